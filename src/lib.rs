@@ -11,15 +11,187 @@
 
 #![warn(missing_docs)]
 
-use std::{convert, env, fmt, io, path::PathBuf, process, string};
+use std::{
+    convert, env, fmt, io,
+    path::{Path, PathBuf},
+    process, string,
+};
 
 /// Returns the Cargo manifest path of the surrounding crate.
 ///
-/// The path is retrieved by parsing the output of `cargo locate-project`.
+/// The path is retrieved by parsing the output of `cargo locate-project`. If the
+/// `cargo` binary cannot be executed, this falls back to walking the filesystem
+/// upwards from the current directory (see [`locate_manifest_in`]).
 pub fn locate_manifest() -> Result<PathBuf, LocateManifestError> {
+    match run_locate_project(&[], MessageFormat::Json) {
+        Err(LocateManifestError::Io(_)) => locate_manifest_in(&env::current_dir()?),
+        result => result,
+    }
+}
+
+/// Returns the Cargo manifest path of the surrounding crate, requesting the given
+/// `cargo locate-project` message format.
+///
+/// Using [`MessageFormat::Plain`] skips the JSON parsing step entirely, which avoids
+/// the [`LocateManifestError::ParseJson`] and [`LocateManifestError::NoRoot`] failure
+/// cases and keeps working even if the JSON schema of `cargo locate-project` changes
+/// in a future cargo release.
+pub fn locate_manifest_with_format(
+    format: MessageFormat,
+) -> Result<PathBuf, LocateManifestError> {
+    run_locate_project(&[], format)
+}
+
+/// Returns the Cargo manifest path of the crate that contains `start`, without
+/// invoking `cargo` as a subprocess.
+///
+/// This ascends from `start` towards the filesystem root, returning the first
+/// directory that contains a `Cargo.toml`. Unlike [`locate_workspace_manifest`], this
+/// does not distinguish workspace members from the workspace root; it simply returns
+/// the nearest manifest.
+pub fn locate_manifest_in(start: &Path) -> Result<PathBuf, LocateManifestError> {
+    let mut dir = start.canonicalize()?;
+    loop {
+        let candidate = dir.join("Cargo.toml");
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+        dir = match dir.parent() {
+            Some(parent) => parent.to_owned(),
+            None => return Err(LocateManifestError::NotFound),
+        };
+    }
+}
+
+/// Returns the Cargo manifest path of the workspace root that the surrounding crate
+/// belongs to.
+///
+/// The path is retrieved by parsing the output of `cargo locate-project --workspace`.
+/// If the surrounding crate is not part of a workspace, this returns the same path as
+/// [`locate_manifest`].
+pub fn locate_workspace_manifest() -> Result<PathBuf, LocateManifestError> {
+    run_locate_project(&["--workspace"], MessageFormat::Json)
+}
+
+/// Returns the Cargo manifest path of the workspace root, requesting the given
+/// `cargo locate-project` message format.
+///
+/// See [`locate_manifest_with_format`] for details on [`MessageFormat`].
+pub fn locate_workspace_manifest_with_format(
+    format: MessageFormat,
+) -> Result<PathBuf, LocateManifestError> {
+    run_locate_project(&["--workspace"], format)
+}
+
+/// The output format requested from `cargo locate-project`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Request the default JSON output and parse the `root` field out of it.
+    Json,
+    /// Request `--message-format plain`, which prints just the manifest path with no
+    /// JSON wrapper.
+    Plain,
+}
+
+/// Returns the Cargo manifest path of the given direct dependency of the surrounding
+/// crate.
+///
+/// The path is retrieved by running `cargo metadata` and cross-referencing the
+/// resolved dependency graph: the current package's node in `resolve.nodes` is found
+/// via `resolve.root`, its `deps` array is searched for an entry whose name matches
+/// `name`, and the resulting package id is looked up in the top-level `packages` array.
+pub fn locate_dependency_manifest(name: &str) -> Result<PathBuf, LocateManifestError> {
+    let metadata = run_cargo_metadata(&[])?;
+
+    let root_id = metadata["resolve"]["root"]
+        .as_str()
+        .ok_or(LocateManifestError::InvalidMetadata)?;
+    let node = metadata["resolve"]["nodes"]
+        .members()
+        .find(|node| node["id"].as_str() == Some(root_id))
+        .ok_or(LocateManifestError::InvalidMetadata)?;
+    let dep_id = node["deps"]
+        .members()
+        .find(|dep| dep["name"].as_str() == Some(name))
+        .and_then(|dep| dep["pkg"].as_str())
+        .ok_or_else(|| LocateManifestError::DependencyNotFound {
+            name: name.to_owned(),
+        })?;
+
+    let manifest_path = metadata["packages"]
+        .members()
+        .find(|package| package["id"].as_str() == Some(dep_id))
+        .and_then(|package| package["manifest_path"].as_str())
+        .ok_or(LocateManifestError::InvalidMetadata)?;
+    Ok(PathBuf::from(manifest_path))
+}
+
+/// Returns the Cargo manifest path of every member of the workspace the surrounding
+/// crate belongs to.
+///
+/// The paths are retrieved by running `cargo metadata --no-deps`, reading the
+/// top-level `workspace_members` id list, and resolving each id against the
+/// `manifest_path` of the matching entry in the top-level `packages` array.
+pub fn locate_workspace_members() -> Result<Vec<PathBuf>, LocateManifestError> {
+    let metadata = run_cargo_metadata(&["--no-deps"])?;
+
+    let member_ids = metadata["workspace_members"]
+        .members()
+        .map(|id| id.as_str().ok_or(LocateManifestError::InvalidMetadata))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    member_ids
+        .into_iter()
+        .map(|id| {
+            metadata["packages"]
+                .members()
+                .find(|package| package["id"].as_str() == Some(id))
+                .and_then(|package| package["manifest_path"].as_str())
+                .map(PathBuf::from)
+                .ok_or(LocateManifestError::InvalidMetadata)
+        })
+        .collect()
+}
+
+/// Runs `cargo locate-project` with the given extra arguments and the given message
+/// format, returning the manifest path it reports.
+fn run_locate_project(
+    extra_args: &[&str],
+    format: MessageFormat,
+) -> Result<PathBuf, LocateManifestError> {
+    let cargo = env::var("CARGO").unwrap_or("cargo".to_owned());
+    let mut command = process::Command::new(cargo);
+    command.arg("locate-project").args(extra_args);
+    if format == MessageFormat::Plain {
+        command.arg("--message-format").arg("plain");
+    }
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(LocateManifestError::CargoExecution {
+            stderr: output.stderr,
+        });
+    }
+
+    let output = String::from_utf8(output.stdout)?;
+    match format {
+        MessageFormat::Plain => Ok(PathBuf::from(output.trim())),
+        MessageFormat::Json => {
+            let parsed = json::parse(&output)?;
+            let root = parsed["root"].as_str().ok_or(LocateManifestError::NoRoot)?;
+            Ok(PathBuf::from(root))
+        }
+    }
+}
+
+/// Runs `cargo metadata --format-version 1` with the given extra arguments and parses
+/// its stdout as JSON.
+fn run_cargo_metadata(extra_args: &[&str]) -> Result<json::JsonValue, LocateManifestError> {
     let cargo = env::var("CARGO").unwrap_or("cargo".to_owned());
     let output = process::Command::new(cargo)
-        .arg("locate-project")
+        .arg("metadata")
+        .arg("--format-version")
+        .arg("1")
+        .args(extra_args)
         .output()?;
     if !output.status.success() {
         return Err(LocateManifestError::CargoExecution {
@@ -28,9 +200,7 @@ pub fn locate_manifest() -> Result<PathBuf, LocateManifestError> {
     }
 
     let output = String::from_utf8(output.stdout)?;
-    let parsed = json::parse(&output)?;
-    let root = parsed["root"].as_str().ok_or(LocateManifestError::NoRoot)?;
-    Ok(PathBuf::from(root))
+    Ok(json::parse(&output)?)
 }
 
 /// Errors that can occur while retrieving the cargo manifest path.
@@ -49,6 +219,16 @@ pub enum LocateManifestError {
     ParseJson(json::Error),
     /// The JSON output of `cargo locate-project` did not contain the expected "root" string.
     NoRoot,
+    /// No dependency with the given name was found among the resolved dependencies of
+    /// the surrounding crate.
+    DependencyNotFound {
+        /// The name that was searched for.
+        name: String,
+    },
+    /// The JSON output of `cargo metadata` did not have the expected shape.
+    InvalidMetadata,
+    /// No `Cargo.toml` was found in the starting directory or any of its ancestors.
+    NotFound,
 }
 
 impl fmt::Display for LocateManifestError {
@@ -70,6 +250,15 @@ impl fmt::Display for LocateManifestError {
             LocateManifestError::NoRoot => {
                 write!(f, "The JSON output of `cargo locate-project` did not contain the expected \"root\" string.")
             }
+            LocateManifestError::DependencyNotFound { name } => {
+                write!(f, "No dependency named \"{}\" was found among the resolved dependencies of the surrounding crate.", name)
+            }
+            LocateManifestError::InvalidMetadata => {
+                write!(f, "The JSON output of `cargo metadata` did not have the expected shape.")
+            }
+            LocateManifestError::NotFound => {
+                write!(f, "No `Cargo.toml` was found in the starting directory or any of its ancestors.")
+            }
         }
     }
 }
@@ -82,6 +271,9 @@ impl std::error::Error for LocateManifestError {
             LocateManifestError::StringConversion(err) => Some(err),
             LocateManifestError::ParseJson(err) => Some(err),
             LocateManifestError::NoRoot => None,
+            LocateManifestError::DependencyNotFound { name: _ } => None,
+            LocateManifestError::InvalidMetadata => None,
+            LocateManifestError::NotFound => None,
         }
     }
 }
@@ -117,3 +309,68 @@ fn test_manifest_path() {
         .unwrap();
     assert_eq!(manifest_path, manual_path);
 }
+
+#[test]
+fn test_workspace_manifest_path() {
+    let manifest_path =
+        locate_workspace_manifest().expect("failed to retrieve workspace manifest path");
+    assert!(manifest_path.ends_with("Cargo.toml"));
+}
+
+#[test]
+fn test_manifest_with_plain_format() {
+    let manifest_path = locate_manifest_with_format(MessageFormat::Plain)
+        .expect("failed to retrieve cargo manifest path");
+    assert_eq!(
+        manifest_path,
+        locate_manifest().expect("failed to retrieve cargo manifest path")
+    );
+}
+
+#[test]
+fn test_dependency_manifest_path() {
+    let manifest_path =
+        locate_dependency_manifest("json").expect("failed to retrieve dependency manifest path");
+    assert!(manifest_path.ends_with("Cargo.toml"));
+}
+
+#[test]
+fn test_workspace_members() {
+    let members = locate_workspace_members().expect("failed to retrieve workspace members");
+    let own_manifest = locate_manifest().expect("failed to retrieve cargo manifest path");
+    assert!(members.contains(&own_manifest));
+}
+
+#[test]
+fn test_locate_manifest_in_ascends_to_ancestor() {
+    use std::fs;
+
+    let root = env::temp_dir().join(format!(
+        "locate_cargo_manifest_test_ascend_{}",
+        process::id()
+    ));
+    let nested = root.join("b").join("c");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(root.join("Cargo.toml"), "").unwrap();
+
+    let manifest_path = locate_manifest_in(&nested).expect("failed to locate manifest");
+    assert_eq!(manifest_path, root.canonicalize().unwrap().join("Cargo.toml"));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_locate_manifest_in_not_found() {
+    use std::fs;
+
+    let root = env::temp_dir().join(format!(
+        "locate_cargo_manifest_test_not_found_{}",
+        process::id()
+    ));
+    fs::create_dir_all(&root).unwrap();
+
+    let result = locate_manifest_in(&root);
+    assert!(matches!(result, Err(LocateManifestError::NotFound)));
+
+    fs::remove_dir_all(&root).unwrap();
+}